@@ -0,0 +1,102 @@
+use anyhow::Result;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::reddit::Post;
+
+/// Raw, as-configured filter rules for a subreddit.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct FilterConfig {
+    #[serde(default)]
+    authors_allow: Vec<String>,
+    #[serde(default)]
+    authors_deny: Vec<String>,
+    #[serde(default)]
+    title_allow: Vec<String>,
+    #[serde(default)]
+    title_deny: Vec<String>,
+    #[serde(default)]
+    flair_allow: Vec<String>,
+    #[serde(default)]
+    flair_deny: Vec<String>,
+    #[serde(default)]
+    exclude_nsfw: bool,
+    #[serde(default)]
+    exclude_stickied: bool,
+}
+
+/// A [`FilterConfig`] with its regexes pre-compiled, ready to test posts
+/// against.
+#[derive(Deserialize, Debug, Default)]
+#[serde(try_from = "FilterConfig")]
+pub struct Filter {
+    authors_allow: Vec<String>,
+    authors_deny: Vec<String>,
+    title_allow: Vec<Regex>,
+    title_deny: Vec<Regex>,
+    flair_allow: Vec<String>,
+    flair_deny: Vec<String>,
+    exclude_nsfw: bool,
+    exclude_stickied: bool,
+}
+
+impl TryFrom<FilterConfig> for Filter {
+    type Error = anyhow::Error;
+
+    fn try_from(config: FilterConfig) -> Result<Self> {
+        let compile = |patterns: Vec<String>| -> Result<Vec<Regex>> {
+            patterns.iter().map(|p| Ok(Regex::new(p)?)).collect()
+        };
+
+        Ok(Filter {
+            authors_allow: config.authors_allow,
+            authors_deny: config.authors_deny,
+            title_allow: compile(config.title_allow)?,
+            title_deny: compile(config.title_deny)?,
+            flair_allow: config.flair_allow,
+            flair_deny: config.flair_deny,
+            exclude_nsfw: config.exclude_nsfw,
+            exclude_stickied: config.exclude_stickied,
+        })
+    }
+}
+
+impl Filter {
+    /// Whether `post` passes every configured rule and should be notified.
+    pub fn allows(&self, post: &Post) -> bool {
+        let flags = post.flags();
+        if self.exclude_nsfw && flags.nsfw {
+            return false;
+        }
+        if self.exclude_stickied && flags.stickied {
+            return false;
+        }
+
+        if !self.authors_allow.is_empty() && !self.authors_allow.iter().any(|a| a == &post.author)
+        {
+            return false;
+        }
+        if self.authors_deny.iter().any(|a| a == &post.author) {
+            return false;
+        }
+
+        let flair = post.link_flair_text.as_deref().unwrap_or("");
+        if !self.flair_allow.is_empty() && !self.flair_allow.iter().any(|f| f == flair) {
+            return false;
+        }
+        if !flair.is_empty() && self.flair_deny.iter().any(|f| f == flair) {
+            return false;
+        }
+
+        if !self.title_allow.is_empty()
+            && !self.title_allow.iter().any(|re| re.is_match(&post.title))
+        {
+            return false;
+        }
+        if self.title_deny.iter().any(|re| re.is_match(&post.title)) {
+            return false;
+        }
+
+        true
+    }
+}
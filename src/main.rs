@@ -1,185 +1,203 @@
-use std::io::ErrorKind;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
-use futures::stream::StreamExt;
-use reddit_api::structs::{Post, SubredditSort};
-use reddit_api::RedditClient;
 use serde::Deserialize;
-use time::format_description::well_known::Iso8601;
 use time::OffsetDateTime;
 use tokio::fs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
 use tokio::time::MissedTickBehavior;
 
-const LAST_ID_FILE: &str = "last_seen.txt";
+mod filter;
+mod notifier;
+mod reddit;
+mod server;
+mod state;
+
+use filter::Filter;
+use notifier::NotifierConfig;
+use reddit::RateLimiter;
+use server::{Health, ScanRequest};
+use state::Store;
+
+const DB_FILE: &str = "sub_notif.db";
 const CONFIG_FILE: &str = "config.yaml";
-const REQ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
-const NUM_POSTS: usize = 50;
+const CONTROL_ADDR: &str = "0.0.0.0:8080";
 
 #[derive(Deserialize, Debug)]
 struct Config {
-    pushover_token: String,
-    pushover_user: String,
-    subreddit: String,
+    subreddits: Vec<SubredditConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SubredditConfig {
+    name: String,
+    /// Display title used in the notification, defaults to `name`.
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    filter: Filter,
+    notifiers: Vec<NotifierConfig>,
+}
+
+impl SubredditConfig {
+    fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
 }
 
 #[tokio::main]
 async fn main() {
     // parse config file
     let config = &parse_config().await;
-    let subreddit = &config.subreddit;
-    let reddit_client = RedditClient::new().unwrap();
     let client = reqwest::Client::builder()
         .use_rustls_tls()
-        .timeout(REQ_TIMEOUT)
+        .timeout(std::time::Duration::from_secs(10))
         .build()
         .unwrap();
-    let mut last_id = last_id().await.unwrap();
+    let limiter = Arc::new(RateLimiter::new());
+    let store = Arc::new(Store::open(DB_FILE).unwrap());
+    let health = Arc::new(Health::default());
+    let mut last_top_ids: HashMap<String, u64> = HashMap::new();
+
+    let (scan_tx, mut scan_rx) = mpsc::channel::<ScanRequest>(8);
+    tokio::spawn(server::run(
+        CONTROL_ADDR.parse().unwrap(),
+        health.clone(),
+        store.clone(),
+        limiter.clone(),
+        scan_tx,
+    ));
 
     let mut interval = tokio::time::interval(Duration::from_secs(10));
     interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
     loop {
-        interval.tick().await;
-
-        // get recent posts
-        let posts = match reddit_posts(&reddit_client, subreddit, &last_id).await {
-            Ok(posts) => posts,
-            Err(_) => continue,
-        };
-
-        // send notifications via pushover
-        if !posts.is_empty() {
-            match pushover(config, &client, &posts).await {
-                Ok(_) => {
-                    match parse_id(&posts[0].id) {
-                        Ok(id) => last_id = Some(id),
-                        Err(_) => eprintln!("Invalid post id {}", &posts[0].id),
+        tokio::select! {
+            _ = interval.tick() => {
+                let mut all_ok = true;
+                for sub in &config.subreddits {
+                    match poll_subreddit(sub, &client, &limiter, &store, &mut last_top_ids).await {
+                        Ok(notified) if notified > 0 => {
+                            println!("r/{}: notified {} post(s)", sub.name, notified);
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            all_ok = false;
+                            eprintln!("Failed to poll r/{}: {}", sub.name, e);
+                        }
+                    }
+                    if let Ok(Some(cursor)) = store.last_id(&sub.name) {
+                        health.cursors.lock().await.insert(sub.name.clone(), cursor);
                     }
                 }
-                Err(e) => eprintln!("Pushover error: {}", e),
+                if all_ok {
+                    *health.last_poll_at.lock().await = Some(OffsetDateTime::now_utc());
+                }
+            }
+            Some(ScanRequest::Scan { subreddit, reply }) = scan_rx.recv() => {
+                let result = match config.subreddits.iter().find(|s| s.name == subreddit) {
+                    Some(sub) => poll_subreddit(sub, &client, &limiter, &store, &mut last_top_ids).await,
+                    None => Err(anyhow::anyhow!("no configured subreddit named {}", subreddit)),
+                };
+                let _ = reply.send(result);
             }
         }
     }
 }
 
-async fn parse_config() -> Config {
-    let contents = fs::read_to_string(CONFIG_FILE).await.unwrap_or_else(|_| {
-        eprintln!("Error: failed to open file {}", CONFIG_FILE);
-        panic!();
-    });
-
-    serde_yaml::from_str(&contents).unwrap_or_else(|err| {
-        eprintln!("Error parsing {}: {:?}", CONFIG_FILE, err);
-        panic!();
-    })
-}
+/// Fetches, filters, and notifies for a single subreddit's new posts,
+/// returning how many were actually delivered.
+async fn poll_subreddit(
+    sub: &SubredditConfig,
+    client: &reqwest::Client,
+    limiter: &RateLimiter,
+    store: &Store,
+    last_top_ids: &mut HashMap<String, u64>,
+) -> Result<usize> {
+    let last_id = store.last_id(&sub.name)?;
+    let last_top_id = last_top_ids.get(&sub.name).copied();
+
+    let outcome = match reddit::fetch_new_posts(client, limiter, &sub.name, last_id, last_top_id)
+        .await?
+    {
+        Some(outcome) => outcome,
+        None => return Ok(0), // feed unchanged since last poll
+    };
 
-async fn last_id() -> Result<Option<u64>> {
-    let mut f = match fs::File::open(LAST_ID_FILE).await {
-        Ok(f) => f,
-        Err(e) => {
-            if e.kind() == ErrorKind::NotFound {
-                return Ok(None);
+    // process oldest-to-newest, skipping posts already recorded as handled
+    // and fanning the rest out to every configured notifier
+    let mut notified = 0;
+    let mut all_recorded = true;
+    for post in outcome.posts.iter().rev() {
+        match store.was_notified(&sub.name, &post.id) {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("Failed to check notified state for {}: {}", post.id, e);
+                all_recorded = false;
+                continue;
             }
-            return Err(e.into());
         }
-    };
-
-    let mut last_id = String::new();
-    f.read_to_string(&mut last_id).await?;
-    Ok(Some(parse_id(&last_id)?))
-}
 
-async fn reddit_posts(
-    client: &RedditClient,
-    subreddit: &str,
-    last_id: &Option<u64>,
-) -> Result<Vec<Post>> {
-    let query = client
-        .subreddit_posts_query()
-        .subreddit(subreddit)
-        .sort(SubredditSort::New)
-        .build();
-    let mut stream = query.execute().await.take(NUM_POSTS);
-    let mut posts = Vec::new();
-    while let Some(post) = stream.next().await {
-        let post = post?;
-        if let Some(last_id) = last_id {
-            if parse_id(&post.id)? <= *last_id {
-                break;
+        if !sub.filter.allows(post) {
+            // filtered posts are recorded as handled so they aren't
+            // re-evaluated, but never reach a notifier
+            if let Err(e) = store.record_notified(&sub.name, &post.id) {
+                eprintln!("Failed to record filtered post {}: {}", post.id, e);
+                all_recorded = false;
             }
+            continue;
         }
-        posts.push(post);
-    }
-    Ok(posts)
-}
 
-fn parse_id(id: &str) -> Result<u64> {
-    Ok(u64::from_str_radix(id.trim().trim_start_matches("t3_"), 36)?)
-}
-
-async fn pushover(config: &Config, client: &reqwest::Client, posts: &[Post]) -> Result<()> {
-    const POST_TIMEOUT_RETRY: i32 = 3;
-
-    for post in posts.iter().rev() {
-        for attempt in 0..POST_TIMEOUT_RETRY {
-            // set parameters
-            let timestamp =
-                OffsetDateTime::parse(&post.created_at, &Iso8601::DEFAULT)?.unix_timestamp();
-            let decoded_title = htmlescape::decode_html(&post.title).unwrap_or(post.title.clone());
-            let params = [
-                ("token", &config.pushover_token),
-                ("user", &config.pushover_user),
-                ("title", &format!("New post on r/{}", "dreamcatcher")),
-                ("message", &decoded_title),
-                ("url", &format!("https://redd.it/{}", post.id.trim_start_matches("t3_"))),
-                ("timestamp", &timestamp.to_string()),
-            ];
-
-            // send POST
-            let resp = client
-                .post("https://api.pushover.net/1/messages.json")
-                .form(&params)
-                .send()
-                .await;
-
-            // check if POST is ok
-            let resp = match resp {
-                Ok(r) => r,
+        let mut all_delivered = true;
+        for notifier_config in &sub.notifiers {
+            match notifier_config.as_notifier().notify(client, post, sub.title()).await {
+                Ok(_) => {}
                 Err(e) => {
-                    if e.is_timeout() {
-                        // retry if timed out
-                        eprintln!(
-                            "POST {} to pushover timed out (attempt {} of {})",
-                            post.id,
-                            attempt + 1,
-                            POST_TIMEOUT_RETRY
-                        );
-                        continue;
-                    } else {
-                        // break if failed for other reason
-                        eprintln!("{:?}", e);
-                        break;
-                    }
-                }
-            };
-
-            // record last successful push
-            if resp.status().is_success() {
-                match resp.text().await {
-                    Err(e) => eprintln!("{:?}", e), // could not parse resp body
-                    Ok(_) => {
-                        let mut f = fs::File::create(LAST_ID_FILE).await.unwrap();
-                        f.write_all(post.id.as_bytes()).await.unwrap();
-                        println!("{}", &post.id);
-                    }
+                    all_delivered = false;
+                    eprintln!("Notifier error for r/{}: {}", sub.name, e);
                 }
             }
+        }
 
+        // only record once every configured notifier has succeeded, so a
+        // failure on any backend gets the whole post retried next poll
+        // rather than silently dropping the backends that failed
+        if all_delivered {
+            if let Err(e) = store.record_notified(&sub.name, &post.id) {
+                eprintln!("Failed to record notified post {}: {}", post.id, e);
+                all_recorded = false;
+            }
+            notified += 1;
+        } else {
+            // stop here: recording a newer post would move the id cursor
+            // past this one and it would never be fetched again
+            all_recorded = false;
             break;
         }
     }
 
-    Ok(())
+    // only advance the unchanged-feed cursor once every post in this batch
+    // has been recorded; otherwise a failed post would never be retried,
+    // since the next poll would see the same top id and skip entirely
+    if all_recorded {
+        if let Some(top_id) = outcome.top_id {
+            last_top_ids.insert(sub.name.clone(), top_id);
+        }
+    }
+
+    Ok(notified)
+}
+
+async fn parse_config() -> Config {
+    let contents = fs::read_to_string(CONFIG_FILE).await.unwrap_or_else(|_| {
+        eprintln!("Error: failed to open file {}", CONFIG_FILE);
+        panic!();
+    });
+
+    serde_yaml::from_str(&contents).unwrap_or_else(|err| {
+        eprintln!("Error parsing {}: {:?}", CONFIG_FILE, err);
+        panic!();
+    })
 }
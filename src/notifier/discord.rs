@@ -0,0 +1,37 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crate::reddit::Post;
+use serde::Deserialize;
+use serde_json::json;
+use time::OffsetDateTime;
+
+#[derive(Deserialize, Debug)]
+pub struct DiscordNotifier {
+    webhook_url: String,
+}
+
+#[async_trait]
+impl super::Notifier for DiscordNotifier {
+    async fn notify(&self, client: &reqwest::Client, post: &Post, title: &str) -> Result<()> {
+        let decoded_title = post.decoded_title();
+        let url = post.short_url();
+        let timestamp = OffsetDateTime::from_unix_timestamp(post.created_utc as i64)?
+            .format(&time::format_description::well_known::Rfc3339)?;
+
+        let body = json!({
+            "embeds": [{
+                "title": decoded_title,
+                "url": url,
+                "author": { "name": format!("New post on r/{}", title) },
+                "timestamp": timestamp,
+            }]
+        });
+
+        let resp = client.post(&self.webhook_url).json(&body).send().await?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("discord webhook returned {}", resp.status()))
+        }
+    }
+}
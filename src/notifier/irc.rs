@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use crate::reddit::Post;
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, ReadHalf, WriteHalf};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// How long to wait for the server to finish registration (send a `001`
+/// welcome) before giving up.
+const REGISTRATION_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize, Debug)]
+pub struct IrcNotifier {
+    server: String,
+    port: u16,
+    nick: String,
+    channel: String,
+}
+
+#[async_trait]
+impl super::Notifier for IrcNotifier {
+    async fn notify(&self, _client: &reqwest::Client, post: &Post, title: &str) -> Result<()> {
+        let nick = sanitize(&self.nick);
+        let channel = sanitize(&self.channel);
+        let message = sanitize(&format!(
+            "New post on r/{}: {} ({})",
+            title,
+            post.decoded_title(),
+            post.short_url()
+        ));
+
+        let stream = TcpStream::connect((self.server.as_str(), self.port)).await?;
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines = BufReader::new(reader).lines();
+
+        writer
+            .write_all(format!("NICK {}\r\n", nick).as_bytes())
+            .await?;
+        writer
+            .write_all(format!("USER {} 0 * :{}\r\n", nick, nick).as_bytes())
+            .await?;
+
+        timeout(
+            REGISTRATION_TIMEOUT,
+            await_registration(&mut lines, &mut writer),
+        )
+        .await
+        .context("timed out waiting for IRC registration")??;
+
+        writer
+            .write_all(format!("JOIN {}\r\n", channel).as_bytes())
+            .await?;
+        writer
+            .write_all(format!("PRIVMSG {} :{}\r\n", channel, message).as_bytes())
+            .await?;
+        writer.write_all(b"QUIT\r\n").await?;
+        writer.shutdown().await?;
+
+        Ok(())
+    }
+}
+
+/// Reads server lines until the `001` welcome numeric is seen, answering any
+/// `PING` the server sends during registration (the anti-spoof check most
+/// servers require before completing it).
+async fn await_registration(
+    lines: &mut tokio::io::Lines<BufReader<ReadHalf<TcpStream>>>,
+    writer: &mut WriteHalf<TcpStream>,
+) -> Result<()> {
+    while let Some(line) = lines.next_line().await? {
+        let mut words = line.splitn(2, ' ');
+        if words.next() == Some("PING") {
+            let token = words.next().unwrap_or("");
+            writer
+                .write_all(format!("PONG {}\r\n", token).as_bytes())
+                .await?;
+            continue;
+        }
+        if line.splitn(3, ' ').nth(1) == Some("001") {
+            return Ok(());
+        }
+    }
+    Err(anyhow!("connection closed before registration completed"))
+}
+
+/// Strips CR/LF from untrusted text before it's formatted into a raw IRC
+/// protocol line, so a post title can't inject extra commands.
+fn sanitize(s: &str) -> String {
+    s.replace(['\r', '\n'], " ")
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crate::reddit::Post;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize, Debug)]
+pub struct MatrixNotifier {
+    homeserver: String,
+    access_token: String,
+    room_id: String,
+}
+
+#[async_trait]
+impl super::Notifier for MatrixNotifier {
+    async fn notify(&self, client: &reqwest::Client, post: &Post, title: &str) -> Result<()> {
+        let decoded_title = post.decoded_title();
+        let url = post.short_url();
+        let body = format!("New post on r/{}: {} ({})", title, decoded_title, url);
+
+        let txn_id = post.id.trim_start_matches("t3_");
+        let endpoint = format!(
+            "{}/_matrix/client/r0/rooms/{}/send/m.room.message/{}",
+            self.homeserver.trim_end_matches('/'),
+            self.room_id,
+            txn_id
+        );
+
+        let resp = client
+            .put(&endpoint)
+            .bearer_auth(&self.access_token)
+            .json(&json!({ "msgtype": "m.text", "body": body }))
+            .send()
+            .await?;
+
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("matrix send returned {}", resp.status()))
+        }
+    }
+}
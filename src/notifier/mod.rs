@@ -0,0 +1,44 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crate::reddit::Post;
+use serde::Deserialize;
+
+mod discord;
+mod irc;
+mod matrix;
+mod pushover;
+
+pub use discord::DiscordNotifier;
+pub use irc::IrcNotifier;
+pub use matrix::MatrixNotifier;
+pub use pushover::PushoverNotifier;
+
+/// A destination that new posts can be delivered to.
+#[async_trait]
+pub trait Notifier {
+    /// Sends a notification for `post`. `client` is a shared HTTP client for
+    /// backends that need one; `title` is the display name to use for the
+    /// subreddit the post came from.
+    async fn notify(&self, client: &reqwest::Client, post: &Post, title: &str) -> Result<()>;
+}
+
+/// One configured delivery backend for a subreddit.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum NotifierConfig {
+    Pushover(PushoverNotifier),
+    Discord(DiscordNotifier),
+    Matrix(MatrixNotifier),
+    Irc(IrcNotifier),
+}
+
+impl NotifierConfig {
+    pub fn as_notifier(&self) -> &dyn Notifier {
+        match self {
+            NotifierConfig::Pushover(n) => n,
+            NotifierConfig::Discord(n) => n,
+            NotifierConfig::Matrix(n) => n,
+            NotifierConfig::Irc(n) => n,
+        }
+    }
+}
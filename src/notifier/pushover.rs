@@ -0,0 +1,64 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crate::reddit::Post;
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+const POST_TIMEOUT_RETRY: i32 = 3;
+
+#[derive(Deserialize, Debug)]
+pub struct PushoverNotifier {
+    token: String,
+    user: String,
+}
+
+#[async_trait]
+impl super::Notifier for PushoverNotifier {
+    async fn notify(&self, client: &reqwest::Client, post: &Post, title: &str) -> Result<()> {
+        let timestamp = OffsetDateTime::from_unix_timestamp(post.created_utc as i64)?.unix_timestamp();
+        let decoded_title = post.decoded_title();
+        let title = format!("New post on r/{}", title);
+        let url = post.short_url();
+        let params = [
+            ("token", &self.token),
+            ("user", &self.user),
+            ("title", &title),
+            ("message", &decoded_title),
+            ("url", &url),
+            ("timestamp", &timestamp.to_string()),
+        ];
+
+        for attempt in 0..POST_TIMEOUT_RETRY {
+            let resp = client
+                .post("https://api.pushover.net/1/messages.json")
+                .form(&params)
+                .send()
+                .await;
+
+            let resp = match resp {
+                Ok(r) => r,
+                Err(e) => {
+                    if e.is_timeout() {
+                        eprintln!(
+                            "POST {} to pushover timed out (attempt {} of {})",
+                            post.id,
+                            attempt + 1,
+                            POST_TIMEOUT_RETRY
+                        );
+                        continue;
+                    } else {
+                        return Err(e.into());
+                    }
+                }
+            };
+
+            return if resp.status().is_success() {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("pushover returned {}", resp.status()))
+            };
+        }
+
+        Err(anyhow::anyhow!("pushover POST timed out {} times", POST_TIMEOUT_RETRY))
+    }
+}
@@ -0,0 +1,215 @@
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+/// Page size used for a normal poll.
+const NUM_POSTS: usize = 50;
+/// Largest page Reddit's listing endpoint will return in one request.
+const MAX_POSTS: usize = 100;
+/// Stop polling once the advertised remaining-request budget drops below this.
+const RATE_LIMIT_THRESHOLD: u16 = 10;
+/// Reddit rejects or rate-limits requests with no (or a generic) User-Agent.
+const USER_AGENT: &str = concat!(
+    "sub_notif/",
+    env!("CARGO_PKG_VERSION"),
+    " (by /u/evanc577)"
+);
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Post {
+    #[serde(rename = "name")]
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    pub created_utc: f64,
+    pub permalink: String,
+    pub subreddit: String,
+    #[serde(default)]
+    pub over_18: bool,
+    #[serde(default)]
+    pub stickied: bool,
+    pub link_flair_text: Option<String>,
+}
+
+impl Post {
+    /// The post's nsfw/stickied flags, grouped for filtering.
+    pub fn flags(&self) -> PostFlags {
+        PostFlags {
+            nsfw: self.over_18,
+            stickied: self.stickied,
+        }
+    }
+
+    /// The post title with HTML entities decoded, falling back to the raw
+    /// title if decoding fails.
+    pub fn decoded_title(&self) -> String {
+        htmlescape::decode_html(&self.title).unwrap_or_else(|_| self.title.clone())
+    }
+
+    /// A short `redd.it` link to the post.
+    pub fn short_url(&self) -> String {
+        format!("https://redd.it/{}", self.id.trim_start_matches("t3_"))
+    }
+}
+
+/// A post's moderation-relevant flags, as used by [`crate::filter::Filter`].
+#[derive(Debug, Clone, Copy)]
+pub struct PostFlags {
+    pub nsfw: bool,
+    pub stickied: bool,
+}
+
+#[derive(Deserialize)]
+struct Listing {
+    data: ListingData,
+}
+
+#[derive(Deserialize)]
+struct ListingData {
+    children: Vec<Thing>,
+}
+
+#[derive(Deserialize)]
+struct Thing {
+    data: Post,
+}
+
+/// Tracks Reddit's advertised rate-limit budget across requests so the
+/// poller can back off before getting throttled instead of after.
+pub struct RateLimiter {
+    remaining: AtomicU16,
+    reset_at: Mutex<Option<OffsetDateTime>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            remaining: AtomicU16::new(u16::MAX),
+            reset_at: Mutex::new(None),
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The remaining-request budget Reddit reported on the last response.
+    pub fn remaining(&self) -> u16 {
+        self.remaining.load(Ordering::Relaxed)
+    }
+
+    /// Sleeps until Reddit's rate-limit window resets if the last response
+    /// indicated we're close to exhausting our budget.
+    async fn wait_if_needed(&self) {
+        if self.remaining.load(Ordering::Relaxed) >= RATE_LIMIT_THRESHOLD {
+            return;
+        }
+        let reset_at = *self.reset_at.lock().unwrap();
+        if let Some(reset_at) = reset_at {
+            let remaining = reset_at - OffsetDateTime::now_utc();
+            if remaining > time::Duration::ZERO {
+                tokio::time::sleep(remaining.unsigned_abs()).await;
+            }
+        }
+    }
+
+    fn update(&self, headers: &reqwest::header::HeaderMap) {
+        if let Some(remaining) = header_f64(headers, "x-ratelimit-remaining") {
+            self.remaining.store(remaining as u16, Ordering::Relaxed);
+        }
+        if let Some(reset_secs) = header_f64(headers, "x-ratelimit-reset") {
+            let reset_at = OffsetDateTime::now_utc() + time::Duration::seconds_f64(reset_secs);
+            *self.reset_at.lock().unwrap() = Some(reset_at);
+        }
+    }
+}
+
+fn header_f64(headers: &reqwest::header::HeaderMap, name: &str) -> Option<f64> {
+    headers.get(name)?.to_str().ok()?.parse().ok()
+}
+
+pub fn parse_id(id: &str) -> Result<u64> {
+    Ok(u64::from_str_radix(id.trim().trim_start_matches("t3_"), 36)?)
+}
+
+/// The result of a poll: any posts newer than `last_id`, plus the id of the
+/// newest post in the listing (used to detect an unchanged feed next time).
+pub struct FetchOutcome {
+    pub posts: Vec<Post>,
+    pub top_id: Option<u64>,
+}
+
+/// Fetches new posts for `subreddit`, newest first.
+///
+/// Returns `None` if the listing's top post is unchanged since `last_top_id`,
+/// meaning there is nothing new to notify about. If every post on the first
+/// page turns out to be newer than `last_id`, we've likely fallen behind, so
+/// the page is widened (up to [`MAX_POSTS`]) and re-fetched.
+pub async fn fetch_new_posts(
+    client: &reqwest::Client,
+    limiter: &RateLimiter,
+    subreddit: &str,
+    last_id: Option<u64>,
+    last_top_id: Option<u64>,
+) -> Result<Option<FetchOutcome>> {
+    let mut limit = NUM_POSTS;
+
+    loop {
+        limiter.wait_if_needed().await;
+
+        let url = format!(
+            "https://www.reddit.com/r/{}/new.json?limit={}",
+            subreddit, limit
+        );
+        let resp = client
+            .get(&url)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .send()
+            .await?;
+        limiter.update(resp.headers());
+        let listing: Listing = resp.json().await?;
+        let all_posts = listing
+            .data
+            .children
+            .into_iter()
+            .map(|thing| thing.data)
+            .collect::<Vec<_>>();
+
+        let top_id = all_posts.first().map(|p| parse_id(&p.id)).transpose()?;
+        if top_id.is_some() && top_id == last_top_id {
+            return Ok(None);
+        }
+
+        let mut posts = Vec::new();
+        let mut fell_behind = last_id.is_some();
+        for post in all_posts {
+            let id = parse_id(&post.id)?;
+            if let Some(last_id) = last_id {
+                if id <= last_id {
+                    fell_behind = false;
+                    break;
+                }
+            }
+            posts.push(post);
+        }
+
+        if fell_behind && limit < MAX_POSTS {
+            limit = (limit * 2).min(MAX_POSTS);
+            continue;
+        }
+
+        if fell_behind {
+            eprintln!(
+                "r/{}: feed fell behind by more than {} posts, oldest new posts were truncated",
+                subreddit, MAX_POSTS
+            );
+        }
+
+        return Ok(Some(FetchOutcome { posts, top_id }));
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use warp::http::StatusCode;
+use warp::Filter;
+
+use crate::reddit::RateLimiter;
+use crate::state::Store;
+
+/// A request the HTTP control surface makes of the poll loop, sent over an
+/// mpsc channel so handlers never talk to Reddit directly.
+pub enum ScanRequest {
+    /// Trigger an immediate out-of-cycle fetch for `subreddit`, replying
+    /// with the number of posts notified.
+    Scan {
+        subreddit: String,
+        reply: oneshot::Sender<anyhow::Result<usize>>,
+    },
+}
+
+/// Liveness info the poll loop updates after every successful pass, read by
+/// the `/healthz` and `/status` handlers.
+#[derive(Default)]
+pub struct Health {
+    pub last_poll_at: Mutex<Option<OffsetDateTime>>,
+    pub cursors: Mutex<HashMap<String, u64>>,
+}
+
+#[derive(Serialize)]
+struct HealthzResponse {
+    status: &'static str,
+    last_poll_at: Option<String>,
+    cursors: HashMap<String, u64>,
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    recent_notifications: Vec<crate::state::Notification>,
+    rate_limit_remaining: u16,
+}
+
+#[derive(Deserialize)]
+struct ScanBody {
+    subreddit: String,
+}
+
+/// Builds and serves the `/healthz`, `/status`, and `/scan` routes until the
+/// process exits.
+pub async fn run(
+    addr: SocketAddr,
+    health: Arc<Health>,
+    store: Arc<Store>,
+    limiter: Arc<RateLimiter>,
+    scan_tx: mpsc::Sender<ScanRequest>,
+) {
+    let healthz = warp::path("healthz").and(warp::get()).and_then({
+        let health = health.clone();
+        move || {
+            let health = health.clone();
+            async move {
+                let last_poll_at = health
+                    .last_poll_at
+                    .lock()
+                    .await
+                    .and_then(|t| t.format(&Rfc3339).ok());
+                let cursors = health.cursors.lock().await.clone();
+                Ok::<_, Infallible>(warp::reply::json(&HealthzResponse {
+                    status: "ok",
+                    last_poll_at,
+                    cursors,
+                }))
+            }
+        }
+    });
+
+    let status = warp::path("status").and(warp::get()).and_then({
+        let store = store.clone();
+        let limiter = limiter.clone();
+        move || {
+            let store = store.clone();
+            let limiter = limiter.clone();
+            async move {
+                let recent_notifications = store.recent_notifications(20).unwrap_or_default();
+                Ok::<_, Infallible>(warp::reply::json(&StatusResponse {
+                    recent_notifications,
+                    rate_limit_remaining: limiter.remaining(),
+                }))
+            }
+        }
+    });
+
+    let scan = warp::path("scan")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and_then(move |body: ScanBody| {
+            let scan_tx = scan_tx.clone();
+            async move {
+                let (reply, reply_rx) = oneshot::channel();
+                if scan_tx
+                    .send(ScanRequest::Scan {
+                        subreddit: body.subreddit,
+                        reply,
+                    })
+                    .await
+                    .is_err()
+                {
+                    return Ok::<_, Infallible>(warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({"error": "poll loop unavailable"})),
+                        StatusCode::SERVICE_UNAVAILABLE,
+                    ));
+                }
+
+                match reply_rx.await {
+                    Ok(Ok(notified)) => Ok(warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({"notified": notified})),
+                        StatusCode::OK,
+                    )),
+                    Ok(Err(e)) => Ok(warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({"error": e.to_string()})),
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    )),
+                    Err(_) => Ok(warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({"error": "poll loop did not respond"})),
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    )),
+                }
+            }
+        });
+
+    let routes = healthz.or(status).or(scan);
+    warp::serve(routes).run(addr).await;
+}
@@ -0,0 +1,99 @@
+use std::sync::Mutex;
+
+use anyhow::Result;
+use rusqlite::Connection;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::reddit;
+
+/// Persisted record of which posts have already been handled, backed by an
+/// embedded SQLite database so state survives crashes and scales to many
+/// subreddits without losing history.
+pub struct Store {
+    conn: Mutex<Connection>,
+}
+
+impl Store {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS notifications (
+                subreddit   TEXT NOT NULL,
+                post_id     TEXT NOT NULL,
+                id_num      INTEGER NOT NULL,
+                notified_at TEXT NOT NULL,
+                PRIMARY KEY (subreddit, post_id)
+            );
+            CREATE INDEX IF NOT EXISTS idx_notifications_cursor
+                ON notifications (subreddit, id_num);",
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The highest post id recorded for `subreddit`, used as the polling
+    /// cursor. `None` means nothing has been seen yet.
+    pub fn last_id(&self, subreddit: &str) -> Result<Option<u64>> {
+        let conn = self.conn.lock().unwrap();
+        let id: Option<i64> = conn.query_row(
+            "SELECT MAX(id_num) FROM notifications WHERE subreddit = ?1",
+            [subreddit],
+            |row| row.get(0),
+        )?;
+        Ok(id.map(|id| id as u64))
+    }
+
+    /// Whether `post_id` has already been recorded as handled for `subreddit`.
+    pub fn was_notified(&self, subreddit: &str, post_id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM notifications WHERE subreddit = ?1 AND post_id = ?2)",
+            (subreddit, post_id),
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// Records that `post_id` has been handled, so it isn't re-evaluated on
+    /// a later poll. Only call this after a confirmed-success delivery (or
+    /// once a post has been deliberately filtered out), so a crash between
+    /// sending and recording leaves the post retryable.
+    pub fn record_notified(&self, subreddit: &str, post_id: &str) -> Result<()> {
+        let id_num = reddit::parse_id(post_id)?;
+        let notified_at = OffsetDateTime::now_utc().format(&Rfc3339)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO notifications (subreddit, post_id, id_num, notified_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            (subreddit, post_id, id_num as i64, notified_at),
+        )?;
+        Ok(())
+    }
+
+    /// The most recently handled posts across all subreddits, newest first.
+    pub fn recent_notifications(&self, limit: usize) -> Result<Vec<Notification>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT subreddit, post_id, notified_at FROM notifications
+             ORDER BY notified_at DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit as i64], |row| {
+            Ok(Notification {
+                subreddit: row.get(0)?,
+                post_id: row.get(1)?,
+                notified_at: row.get(2)?,
+            })
+        })?;
+        rows.collect::<rusqlite::Result<_>>().map_err(Into::into)
+    }
+}
+
+/// A single handled-post record, as returned by [`Store::recent_notifications`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Notification {
+    pub subreddit: String,
+    pub post_id: String,
+    pub notified_at: String,
+}